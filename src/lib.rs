@@ -0,0 +1,709 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use clap::ValueEnum;
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use lz4_flex::block::{compress as lz4_compress, decompress as lz4_decompress};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, prelude::*, BufWriter, Cursor, IoSlice, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::vec;
+
+pub const ZISO_MAGIC: u32 = 0x4F53495A; // ZISO
+pub const CISO_MAGIC: u32 = 0x4F534943; // CISO
+const COMPRESS_THREHOLD: usize = 100;
+pub const HEADER_SIZE: u32 = 0x18; // 24
+pub const BLOCK_SIZE: u32 = 0x800; // 2048
+const VERSION: i8 = 1;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Mode {
+    Default,
+    Fast,
+    Slow,
+}
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let printable = match *self {
+            Mode::Default => "default",
+            Mode::Fast => "fast",
+            Mode::Slow => "slow",
+        };
+        write!(f, "{}", printable)
+    }
+}
+
+/// On-disk container format. Both share the same 24-byte header and
+/// MSB-plain-bit index scheme; only the per-block codec differs.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Zso,
+    Cso,
+}
+
+impl Format {
+    pub fn magic(self) -> u32 {
+        match self {
+            Format::Zso => ZISO_MAGIC,
+            Format::Cso => CISO_MAGIC,
+        }
+    }
+
+    pub fn from_magic(magic: u32) -> Option<Format> {
+        match magic {
+            ZISO_MAGIC => Some(Format::Zso),
+            CISO_MAGIC => Some(Format::Cso),
+            _ => None,
+        }
+    }
+
+    pub fn from_extension(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("cso") => Format::Cso,
+            _ => Format::Zso,
+        }
+    }
+
+    fn codec(self) -> Box<dyn BlockCodec + Send + Sync> {
+        match self {
+            Format::Zso => Box::new(Lz4Codec),
+            Format::Cso => Box::new(DeflateCodec),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let printable = match *self {
+            Format::Zso => "zso",
+            Format::Cso => "cso",
+        };
+        write!(f, "{}", printable)
+    }
+}
+
+/// Per-block encode/decode for a container format. The index/alignment
+/// bookkeeping in `compress_zso`/`decompress_zso`/`ZsoReader` is shared and
+/// does not care which codec produced the bytes.
+trait BlockCodec {
+    fn compress_block(&self, data: &[u8], mode: Mode) -> Vec<u8>;
+    fn decompress_block(&self, data: Vec<u8>, block_size: i32) -> io::Result<Vec<u8>>;
+}
+
+struct Lz4Codec;
+impl BlockCodec for Lz4Codec {
+    fn compress_block(&self, data: &[u8], _mode: Mode) -> Vec<u8> {
+        // lz4_flex only implements the baseline LZ4 block format; unlike
+        // the old C `lz4` bindings it has no acceleration knob or a
+        // high-compression variant, so every `Mode` produces the same
+        // bytes here. CSO still honors `Mode` via `Compression::{fast,
+        // default,best}` in `DeflateCodec` below. `--mode` is a no-op for
+        // `.zso` output; see the CLI help text.
+        lz4_compress(data)
+    }
+
+    fn decompress_block(&self, data: Vec<u8>, block_size: i32) -> io::Result<Vec<u8>> {
+        // The extent read from the index includes any alignment padding
+        // that precedes the *next* block, so `data` may have trailing
+        // garbage past the real compressed stream. Unlike the old C `lz4`
+        // bindings, lz4_flex's block decompressor keeps parsing tokens
+        // until its input runs out and errors on any leftover byte, so we
+        // can't just hand it the padded extent - trim from the end and
+        // retry until it decodes, same as the old bindings' retry loop.
+        let mut compressed = data;
+        loop {
+            match lz4_decompress(&compressed, block_size as usize) {
+                Ok(out) => return Ok(out),
+                Err(e) => {
+                    if compressed.is_empty() {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                    }
+                    compressed.pop();
+                }
+            }
+        }
+    }
+}
+
+struct DeflateCodec;
+impl BlockCodec for DeflateCodec {
+    fn compress_block(&self, data: &[u8], mode: Mode) -> Vec<u8> {
+        let level = match mode {
+            Mode::Fast => Compression::fast(),
+            Mode::Slow => Compression::best(),
+            Mode::Default => Compression::default(),
+        };
+        let mut compressor = Compress::new(level, false);
+        // Room for deflate's worst case on incompressible input (stored
+        // blocks plus framing), so such a block still finishes and comes
+        // back at least as long as `data`, which the caller stores plain.
+        let mut out = Vec::with_capacity(data.len() + data.len() / 8 + 64);
+        let status = compressor
+            .compress_vec(data, &mut out, FlushCompress::Finish)
+            .unwrap();
+
+        // `compress_vec` only ever writes into the vec's existing spare
+        // capacity and never grows it, so output that doesn't fit comes
+        // back `Status::Ok` with `out` silently truncated instead of the
+        // complete compressed block `Status::StreamEnd` guarantees.
+        assert!(
+            status == Status::StreamEnd,
+            "deflate output did not fit in the output buffer"
+        );
+        out
+    }
+
+    fn decompress_block(&self, data: Vec<u8>, block_size: i32) -> io::Result<Vec<u8>> {
+        let mut decompressor = Decompress::new(false);
+        let mut out = vec![0u8; block_size as usize];
+        let status = decompressor
+            .decompress(&data, &mut out, FlushDecompress::Finish)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // `Finish` only guarantees the stream actually completed when it
+        // reports `StreamEnd` with every output byte written; anything else
+        // means `data` was a truncated/corrupt block and `out` is silently
+        // zero-padded past whatever was really decoded.
+        if status != Status::StreamEnd || decompressor.total_out() != block_size as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "deflate stream ended before filling the block",
+            ));
+        }
+        Ok(out)
+    }
+}
+
+/// Parsed ZSO/CSO header plus the block index, shared by `ZsoReader` and
+/// `decompress_zso` so the two don't drift on the alignment/plain-bit math.
+struct Index {
+    format: Format,
+    total_bytes: u64,
+    block_size: u32,
+    align: i8,
+    entries: Vec<u64>,
+}
+
+impl Index {
+    fn read_from<R: Read>(src: &mut R) -> io::Result<Index> {
+        let mut header_buf = [0; HEADER_SIZE as usize];
+        src.read_exact(&mut header_buf)?;
+        let mut header = Cursor::new(header_buf);
+
+        let magic = header.read_u32::<LittleEndian>()?;
+        let header_size = header.read_u32::<LittleEndian>()?;
+        let total_bytes = header.read_u64::<LittleEndian>()?;
+        let block_size = header.read_u32::<LittleEndian>()?;
+        let ver = header.read_i8()?;
+        let align = header.read_i8()?;
+
+        let format = Format::from_magic(magic);
+        if format.is_none()
+            || header_size != HEADER_SIZE
+            || total_bytes == 0
+            || block_size == 0
+            || ver != VERSION
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "ziso file format error",
+            ));
+        }
+
+        let total_block = total_bytes / block_size as u64;
+        let mut index_raw = vec![0; (total_block as usize + 1) * 4];
+        src.read_exact(&mut index_raw)?;
+        let mut index_read = Cursor::new(index_raw);
+        let mut entries = Vec::with_capacity(total_block as usize + 1);
+        for _ in 0..total_block + 1 {
+            entries.push(index_read.read_u32::<LittleEndian>()? as u64);
+        }
+
+        Ok(Index {
+            format: format.unwrap(),
+            total_bytes,
+            block_size,
+            align,
+            entries,
+        })
+    }
+
+    fn total_block(&self) -> u64 {
+        self.total_bytes / self.block_size as u64
+    }
+
+    /// Compressed extent `(offset, len)` for `block`, and whether it's stored plain.
+    fn extent(&self, block: u64) -> (u64, u64, bool) {
+        let mut index = self.entries[block as usize];
+        let plain = index & 0x80000000 > 0;
+        index &= 0x7fffffff;
+        let read_pos = index << self.align;
+
+        let read_size = if plain {
+            self.block_size as u64
+        } else {
+            let index2 = self.entries[block as usize + 1] & 0x7fffffff;
+            // Have to read more bytes if align was set
+            let mut read_size2 = (index2 - index) << self.align;
+            if block == self.total_block() - 1 {
+                read_size2 = self.total_bytes - read_pos;
+            }
+            read_size2
+        };
+
+        (read_pos, read_size, plain)
+    }
+}
+
+/// Seekable view over the *decompressed* contents of a ZSO/CSO file.
+///
+/// The header and index are parsed once up front; every `read`/`seek` then
+/// only ever decodes the 2048-byte blocks the requested range touches,
+/// keeping the last decoded block cached so sequential reads within it
+/// don't re-decompress.
+pub struct ZsoReader<R> {
+    inner: R,
+    codec: Box<dyn BlockCodec + Send + Sync>,
+    index: Index,
+    pos: u64,
+    cache: Option<(u64, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> ZsoReader<R> {
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let index = Index::read_from(&mut inner)?;
+        let codec = index.format.codec();
+        Ok(ZsoReader {
+            inner,
+            codec,
+            index,
+            pos: 0,
+            cache: None,
+        })
+    }
+
+    /// Total size in bytes of the decompressed ISO.
+    pub fn len(&self) -> u64 {
+        self.index.total_bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.total_bytes == 0
+    }
+
+    fn decode_block(&mut self, block: u64) -> io::Result<&[u8]> {
+        if self.cache.as_ref().map(|(b, _)| *b) != Some(block) {
+            let (read_pos, read_size, plain) = self.index.extent(block);
+            self.inner.seek(SeekFrom::Start(read_pos))?;
+            let mut raw = vec![0; read_size as usize];
+            match self.inner.read_exact(&mut raw) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.inner.seek(SeekFrom::Start(read_pos))?;
+                    raw.clear();
+                    self.inner.read_to_end(&mut raw)?;
+                }
+                Err(e) => return Err(e),
+            }
+
+            let data = if plain {
+                raw
+            } else {
+                self.codec
+                    .decompress_block(raw, self.index.block_size as i32)?
+            };
+
+            if data.len() != self.index.block_size as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("block {} decoded to the wrong size", block),
+                ));
+            }
+
+            self.cache = Some((block, data));
+        }
+
+        Ok(&self.cache.as_ref().unwrap().1)
+    }
+}
+
+impl<R: Read + Seek> Read for ZsoReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.index.total_bytes || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_size = self.index.block_size as u64;
+        let block = self.pos / block_size;
+        let intra = (self.pos % block_size) as usize;
+        let remaining = (self.index.total_bytes - self.pos) as usize;
+
+        let data = self.decode_block(block)?;
+        let available = &data[intra..];
+        let n = available.len().min(buf.len()).min(remaining);
+        buf[..n].copy_from_slice(&available[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for ZsoReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.index.total_bytes as i64 + offset,
+            SeekFrom::Current(offset) => self.pos as i64 + offset,
+        };
+
+        if new_pos < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        self.pos = new_pos as u64;
+        Ok(self.pos)
+    }
+}
+
+pub fn decompress_zso(infile: PathBuf, outfile: PathBuf) {
+    let fin = File::open(&infile).unwrap();
+    let mut fout = File::create(&outfile).unwrap();
+    let mut reader = ZsoReader::new(fin).unwrap();
+
+    let total_bytes = reader.len();
+    let total_block = reader.index.total_block();
+
+    println!("Decompress '{:?}' to '{:?}'", infile, outfile);
+    println!("Total File Size {} bytes", total_bytes);
+    println!("block size      {} bytes", reader.index.block_size);
+    println!("total blocks    {} blocks", total_block);
+    println!("index align     {}", reader.index.align);
+
+    let percent_period = total_block / 100;
+    let mut percent_cnt = 0;
+    let mut buf = vec![0u8; reader.index.block_size as usize];
+
+    for block in 0..total_block {
+        percent_cnt += 1;
+        if percent_cnt >= percent_period && percent_period != 0 {
+            percent_cnt = 0;
+            eprint!("decompress {}%\r", block / percent_period);
+        }
+
+        reader.read_exact(&mut buf).unwrap();
+        fout.write_all(&buf).unwrap();
+    }
+
+    println!("ziso decompress completed");
+}
+
+/// Write every byte of `bufs` via `write_vectored`, falling back to plain
+/// `write_all` on whichever buffer a partial write landed inside so we
+/// don't depend on the (still unstable) `IoSlice::advance_slices`.
+fn write_all_vectored<W: Write>(writer: &mut W, bufs: &[IoSlice]) -> io::Result<()> {
+    let total_len: usize = bufs.iter().map(|b| b.len()).sum();
+    let written = writer.write_vectored(bufs)?;
+
+    if written >= total_len {
+        return Ok(());
+    }
+
+    let mut skip = written;
+    for buf in bufs {
+        if skip >= buf.len() {
+            skip -= buf.len();
+            continue;
+        }
+        writer.write_all(&buf[skip..])?;
+        skip = 0;
+    }
+
+    Ok(())
+}
+
+struct CompressJob {
+    block: usize,
+    data: Vec<u8>,
+}
+
+struct CompressResult {
+    block: usize,
+    data: Vec<u8>,
+    plain: bool,
+}
+
+pub fn compress_zso(mode: Mode, threads: usize, format: Format, infile: PathBuf, outfile: PathBuf) {
+    // A `threads` of 0 would spawn no workers, so the feeder thread blocks
+    // forever trying to send onto the zero-capacity job channel; clamp here
+    // rather than trusting every caller of this now-public function to do it.
+    let threads = threads.max(1);
+
+    let mut fin = File::open(&infile).unwrap();
+    let mut fout = BufWriter::with_capacity(4 * 1024 * 1024, File::create(&outfile).unwrap());
+    let codec: Arc<dyn BlockCodec + Send + Sync> = Arc::from(format.codec());
+
+    let total_bytes = fin.metadata().unwrap().len();
+
+    // We have to use alignment on any ZSO files which > 2GB, for MSB bit of index as the plain indicator
+    // If we don't then the index can be larger than 2GB, which its plain indicator was improperly set
+    let align = total_bytes / 2u64.pow(31);
+
+    // Reused across every block instead of allocating a fresh padding Vec each time.
+    let padding = vec![b'X'; 1usize << align];
+
+    let mut header = Cursor::new([0u8; HEADER_SIZE as usize]);
+    header.write_u32::<LittleEndian>(format.magic()).unwrap();
+    header.write_u32::<LittleEndian>(HEADER_SIZE).unwrap();
+    header.write_u64::<LittleEndian>(total_bytes).unwrap();
+    header.write_u32::<LittleEndian>(BLOCK_SIZE).unwrap();
+    header.write_i8(VERSION).unwrap();
+    header.write_i8(align as i8).unwrap();
+    fout.write_all(&header.into_inner()).unwrap();
+
+    let total_block = total_bytes / BLOCK_SIZE as u64;
+
+    let mut index_buf = vec![0u64; total_block as usize + 1];
+    fout.write_all(&vec![0u8; (total_block as usize + 1) * 4])
+        .unwrap();
+
+    println!("Compress '{:?}' to '{:?}' ({})", infile, outfile, format);
+    println!("Total File Size {} bytes", total_bytes);
+    println!("block size      {} bytes", BLOCK_SIZE);
+    println!("index align     {}", 1 << align);
+
+    let mut write_pos = fout.stream_position().unwrap();
+    let percent_period = total_block / 100;
+    let mut percent_cnt: u64 = 0;
+
+    // Workers only ever do the CPU-bound compress() call; reassembly below
+    // stays strictly sequential since write_pos/alignment depend on it.
+    let (job_tx, job_rx) = mpsc::sync_channel::<CompressJob>(threads * 4);
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let (res_tx, res_rx) = mpsc::channel::<CompressResult>();
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let job_rx = Arc::clone(&job_rx);
+            let res_tx = res_tx.clone();
+            let codec = Arc::clone(&codec);
+            scope.spawn(move || loop {
+                let job = job_rx.lock().unwrap().recv();
+                let job = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+
+                let mut zso_data = codec.compress_block(&job.data, mode);
+
+                let plain = 100 * zso_data.len() / job.data.len() >= COMPRESS_THREHOLD;
+                if plain {
+                    zso_data = job.data;
+                }
+
+                res_tx
+                    .send(CompressResult {
+                        block: job.block,
+                        data: zso_data,
+                        plain,
+                    })
+                    .unwrap();
+            });
+        }
+        drop(res_tx);
+
+        scope.spawn(move || {
+            for block in 0..total_block as usize {
+                let mut iso_data = vec![0; BLOCK_SIZE as usize];
+                fin.read_exact(&mut iso_data).unwrap();
+                if job_tx.send(CompressJob { block, data: iso_data }).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut pending: BTreeMap<usize, CompressResult> = BTreeMap::new();
+        let mut block: usize = 0;
+
+        while block < total_block as usize {
+            let result = match pending.remove(&block) {
+                Some(result) => result,
+                None => {
+                    let result = res_rx.recv().unwrap();
+                    pending.insert(result.block, result);
+                    continue;
+                }
+            };
+
+            percent_cnt += 1;
+
+            if percent_cnt >= percent_period && percent_period != 0 {
+                percent_cnt = 0;
+
+                if block == 0 {
+                    eprint!(
+                        "compress {:>3}% average rate {:>3}%\r",
+                        block as u64 / percent_period,
+                        0
+                    );
+                } else {
+                    eprint!(
+                        "compress {:>3}% average rate {:>3}%\r",
+                        block as u64 / percent_period,
+                        100 * write_pos / (block as u64 * 0x800)
+                    );
+                }
+            }
+
+            let align_len = if !write_pos.is_multiple_of(1 << align) {
+                (1 << align) - write_pos % (1 << align)
+            } else {
+                0
+            };
+            write_pos += align_len;
+
+            index_buf[block] = write_pos >> align;
+
+            if result.plain {
+                index_buf[block] |= 0x80000000; // Mark as plain;
+            } else if index_buf[block] & 0x80000000 > 0 {
+                println!("Align error, you have to increase align by 1 or CFW won't be able to read offset above 2 ** 31 bytes");
+            }
+
+            // Padding and the compressed block go out as a single vectored
+            // write instead of two separate write_all syscalls per block.
+            write_all_vectored(
+                &mut fout,
+                &[
+                    IoSlice::new(&padding[..align_len as usize]),
+                    IoSlice::new(&result.data),
+                ],
+            )
+            .unwrap();
+            write_pos += result.data.len() as u64;
+            block += 1;
+        }
+    });
+
+    // Last position (total size)
+    index_buf[total_block as usize] = write_pos >> align;
+
+    // Update index block
+    fout.seek(SeekFrom::Start(HEADER_SIZE as u64)).unwrap();
+    for x in index_buf {
+        fout.write_u32::<LittleEndian>(x as u32).unwrap();
+    }
+    fout.flush().unwrap();
+
+    println!(
+        "ziso compress completed, total size = {:>8} bytes, rate {}%",
+        write_pos,
+        (write_pos * 100 / total_bytes)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Round-trips `data` through `compress_zso` on disk (the only entry
+    /// point that knows how to build a valid header+index) and hands back
+    /// the resulting ZSO bytes for `ZsoReader` to read from memory.
+    fn build_zso(data: &[u8]) -> Vec<u8> {
+        let id = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir();
+        let infile = dir.join(format!("ziso_test_{}_{}_in.bin", std::process::id(), id));
+        let outfile = dir.join(format!("ziso_test_{}_{}_out.zso", std::process::id(), id));
+
+        std::fs::write(&infile, data).unwrap();
+        compress_zso(Mode::Default, 1, Format::Zso, infile.clone(), outfile.clone());
+
+        let bytes = std::fs::read(&outfile).unwrap();
+        let _ = std::fs::remove_file(&infile);
+        let _ = std::fs::remove_file(&outfile);
+        bytes
+    }
+
+    fn sample_data() -> Vec<u8> {
+        // A few full blocks of non-trivial content so both the plain and
+        // compressed-block paths in `Index::extent`/`decode_block` run:
+        // blocks 0 and 2 repeat a short pattern and compress, block 1 is
+        // xorshift noise that LZ4 can't shrink, so it is stored plain.
+        let mut state: u32 = 0x9E37_79B9;
+        (0..BLOCK_SIZE as usize * 3)
+            .map(|i| {
+                if i / BLOCK_SIZE as usize == 1 {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    (state >> 24) as u8
+                } else {
+                    (i % 251) as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lz4_decompress_block_trims_trailing_alignment_padding() {
+        // Index::extent() returns the distance between consecutive *aligned*
+        // index entries, which includes the padding written before the next
+        // block - this only shows up once `align` is nonzero (source ISOs
+        // over 2GiB), which `sample_data()` is far too small to trigger, so
+        // exercise the codec directly against a hand-padded extent instead.
+        let original = &sample_data()[..BLOCK_SIZE as usize];
+        let mut padded = lz4_compress(original);
+        padded.extend_from_slice(&[b'X'; 7]);
+
+        let out = Lz4Codec
+            .decompress_block(padded, BLOCK_SIZE as i32)
+            .unwrap();
+        assert_eq!(out, original);
+    }
+
+    #[test]
+    fn zso_reader_round_trips_sequential_read() {
+        let data = sample_data();
+        let zso = build_zso(&data);
+        let mut reader = ZsoReader::new(Cursor::new(zso)).unwrap();
+
+        assert_eq!(reader.len(), data.len() as u64);
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn zso_reader_seeks_to_block_boundary_and_mid_block() {
+        let data = sample_data();
+        let zso = build_zso(&data);
+        let mut reader = ZsoReader::new(Cursor::new(zso)).unwrap();
+        let block_size = BLOCK_SIZE as u64;
+
+        reader.seek(SeekFrom::Start(block_size)).unwrap();
+        let mut buf = [0u8; 16];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &data[block_size as usize..block_size as usize + 16]);
+
+        let mid = block_size + 500;
+        reader.seek(SeekFrom::Start(mid)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &data[mid as usize..mid as usize + 16]);
+
+        reader.seek(SeekFrom::Current(-16)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &data[mid as usize..mid as usize + 16]);
+
+        reader.seek(SeekFrom::End(-10)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, data[data.len() - 10..]);
+    }
+}